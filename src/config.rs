@@ -0,0 +1,227 @@
+//! Config loading and per-directory discovery.
+//!
+//! By default the effective [`Config`] is the global config (as loaded by
+//! `confy`) with a project-local `.rnotes.toml` layered on top. The local
+//! file is found by walking up from the current directory toward the
+//! filesystem root, the same way cargo discovers `.cargo/config.toml` from
+//! the invocation directory.
+
+use anyhow::{Context, Result};
+use directories::UserDirs;
+use serde_derive::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::PROGNAME;
+
+/// Name of the project-local config file looked up by [`discover_local`].
+pub const LOCAL_CONFIG_NAME: &str = ".rnotes.toml";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Config {
+    pub conf: PathBuf,
+    pub editor: String,
+    pub notes_dir: PathBuf,
+    pub extension: String,
+    pub viewer: String,
+
+    /// Remote cheatsheet sources to fall back to, in order, when `Cat`
+    /// misses locally (e.g. `["cheatsh", "tldr"]`). Empty disables the
+    /// fallback.
+    #[serde(default)]
+    pub remote_sources: Vec<String>,
+
+    /// Whether a remote fallback hit is cached as a local note instead of
+    /// just shown once.
+    #[serde(default = "default_cache_remote")]
+    pub cache_remote: bool,
+
+    /// Size in bytes past which the journal note is rotated.
+    #[serde(default = "default_max_size")]
+    pub max_size: u64,
+
+    /// Number of rotated journal files to keep, oldest discarded past this.
+    #[serde(default = "default_max_files")]
+    pub max_files: usize,
+}
+
+fn default_cache_remote() -> bool {
+    true
+}
+
+fn default_max_size() -> u64 {
+    1024 * 1024
+}
+
+fn default_max_files() -> usize {
+    5
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            editor: "vim".into(),
+            extension: "md".into(),
+            viewer: "cat".into(),
+            conf: confy::get_configuration_file_path(PROGNAME, PROGNAME)
+                .expect("Failed to get configuration file"),
+            notes_dir: UserDirs::new()
+                .expect("Failed to get user directory")
+                .home_dir()
+                .join(PROGNAME)
+                .to_path_buf(),
+            remote_sources: Vec::new(),
+            cache_remote: true,
+            max_size: default_max_size(),
+            max_files: default_max_files(),
+        }
+    }
+}
+
+/// A project-local `.rnotes.toml`: every field is optional, so a project
+/// only needs to pin down what it cares about.
+#[derive(Debug, Default, Deserialize)]
+struct LocalConfig {
+    editor: Option<String>,
+    notes_dir: Option<PathBuf>,
+    extension: Option<String>,
+    viewer: Option<String>,
+    remote_sources: Option<Vec<String>>,
+    cache_remote: Option<bool>,
+    max_size: Option<u64>,
+    max_files: Option<usize>,
+}
+
+/// Where an effective config field was pulled from, for `--conf` to report.
+#[derive(Debug, Clone)]
+pub enum Source {
+    Default,
+    Global(PathBuf),
+    Local(PathBuf),
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Source::Default => write!(f, "default"),
+            Source::Global(p) => write!(f, "global ({})", p.display()),
+            Source::Local(p) => write!(f, "local ({})", p.display()),
+        }
+    }
+}
+
+/// Provenance of each overridable [`Config`] field.
+#[derive(Debug, Clone)]
+pub struct Sources {
+    pub conf: Source,
+    pub editor: Source,
+    pub notes_dir: Source,
+    pub extension: Source,
+    pub viewer: Source,
+    pub remote_sources: Source,
+    pub cache_remote: Source,
+    pub max_size: Source,
+    pub max_files: Source,
+}
+
+/// The effective config plus where each field came from.
+pub struct Loaded {
+    pub config: Config,
+    pub sources: Sources,
+}
+
+/// Walk upward from `start` toward the filesystem root looking for
+/// `.rnotes.toml`, returning the first one found along with its parsed
+/// contents. A file that exists but fails to read or parse is an error,
+/// not a reason to keep walking past it in favor of some other ancestor.
+fn discover_local(start: &Path) -> Result<Option<(PathBuf, LocalConfig)>> {
+    let mut dir = Some(start);
+
+    while let Some(d) = dir {
+        let candidate = d.join(LOCAL_CONFIG_NAME);
+
+        if candidate.is_file() {
+            let contents = fs::read_to_string(&candidate)
+                .with_context(|| format!("Failed to read {}", candidate.display()))?;
+            let local = toml::from_str(&contents)
+                .with_context(|| format!("Failed to parse {}", candidate.display()))?;
+
+            return Ok(Some((candidate, local)));
+        }
+
+        dir = d.parent();
+    }
+
+    Ok(None)
+}
+
+fn apply_local(config: &mut Config, sources: &mut Sources, path: PathBuf, local: LocalConfig) {
+    if let Some(editor) = local.editor {
+        config.editor = editor;
+        sources.editor = Source::Local(path.clone());
+    }
+    if let Some(notes_dir) = local.notes_dir {
+        config.notes_dir = notes_dir;
+        sources.notes_dir = Source::Local(path.clone());
+    }
+    if let Some(extension) = local.extension {
+        config.extension = extension;
+        sources.extension = Source::Local(path.clone());
+    }
+    if let Some(viewer) = local.viewer {
+        config.viewer = viewer;
+        sources.viewer = Source::Local(path.clone());
+    }
+    if let Some(remote_sources) = local.remote_sources {
+        config.remote_sources = remote_sources;
+        sources.remote_sources = Source::Local(path.clone());
+    }
+    if let Some(cache_remote) = local.cache_remote {
+        config.cache_remote = cache_remote;
+        sources.cache_remote = Source::Local(path.clone());
+    }
+    if let Some(max_size) = local.max_size {
+        config.max_size = max_size;
+        sources.max_size = Source::Local(path.clone());
+    }
+    if let Some(max_files) = local.max_files {
+        config.max_files = max_files;
+        sources.max_files = Source::Local(path);
+    }
+}
+
+/// Build the effective config. `force_local` ignores the global config and
+/// starts from [`Config::default`]; `force_global` ignores any project-local
+/// `.rnotes.toml`. With neither, the global config is loaded first and a
+/// discovered local file is layered on top.
+pub fn load(force_local: bool, force_global: bool) -> Result<Loaded> {
+    let (mut config, source) = if force_local {
+        (Config::default(), Source::Default)
+    } else {
+        let global: Config = confy::load(PROGNAME, PROGNAME)?;
+        let conf = global.conf.clone();
+        (global, Source::Global(conf))
+    };
+
+    let mut sources = Sources {
+        conf: source.clone(),
+        editor: source.clone(),
+        notes_dir: source.clone(),
+        extension: source.clone(),
+        viewer: source.clone(),
+        remote_sources: source.clone(),
+        cache_remote: source.clone(),
+        max_size: source.clone(),
+        max_files: source,
+    };
+
+    if !force_global {
+        let cwd = std::env::current_dir()?;
+        if let Some((path, local)) = discover_local(&cwd)? {
+            apply_local(&mut config, &mut sources, path, local);
+        }
+    }
+
+    Ok(Loaded { config, sources })
+}
@@ -0,0 +1,125 @@
+//! Fuzzy subsequence matching for the interactive picker.
+//!
+//! Scoring follows the approach used by fzf/skim/nucleo: find the
+//! subsequence match with the smallest span, then reward matches that land
+//! on word boundaries or run consecutively, so e.g. `wp` ranks `work/plan`
+//! above `swap`.
+
+/// Score `candidate` against `query`, lower being a worse match. Returns
+/// `None` when `query` is not a subsequence of `candidate` at all. An empty
+/// query matches everything with a score of `0`.
+pub fn score(candidate: &str, query: &str) -> Option<i64> {
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    let cand: Vec<char> = candidate.chars().collect();
+    let q: Vec<char> = query.chars().collect();
+
+    let (start, end) = smallest_span(&cand, &q)?;
+
+    let mut total: i64 = 0;
+    let mut qi = 0;
+    let mut prev_matched = false;
+
+    for (i, &c) in cand.iter().enumerate().take(end + 1).skip(start) {
+        if qi < q.len() && c.to_ascii_lowercase() == q[qi].to_ascii_lowercase() {
+            total += 10;
+
+            if prev_matched {
+                total += 15;
+            }
+
+            if i == 0 || !cand[i - 1].is_alphanumeric() {
+                total += 20;
+            }
+
+            prev_matched = true;
+            qi += 1;
+        } else {
+            prev_matched = false;
+        }
+    }
+
+    let span = (end - start + 1) as i64;
+
+    Some(total - span)
+}
+
+/// Rank `candidates` against `query`, descending by [`score`], dropping
+/// anything that doesn't match at all.
+pub fn rank<'a, I>(candidates: I, query: &str) -> Vec<&'a String>
+where
+    I: Iterator<Item = &'a String>,
+{
+    let mut scored: Vec<(&String, i64)> = candidates
+        .filter_map(|c| score(c, query).map(|s| (c, s)))
+        .collect();
+
+    scored.sort_by(|a, b| b.1.cmp(&a.1));
+
+    scored.into_iter().map(|(c, _)| c).collect()
+}
+
+/// Find the shortest span of `cand` that contains `q` as a subsequence.
+fn smallest_span(cand: &[char], q: &[char]) -> Option<(usize, usize)> {
+    let mut best: Option<(usize, usize)> = None;
+
+    for start in 0..cand.len() {
+        let Some(end) = match_from(cand, q, start) else {
+            continue;
+        };
+
+        if best.map_or(true, |(bs, be)| end - start < be - bs) {
+            best = Some((start, end));
+        }
+    }
+
+    best
+}
+
+/// Find the index of the last character of a subsequence match of `q`
+/// starting the search at `start`, or `None` if `q` can't be matched.
+fn match_from(cand: &[char], q: &[char], start: usize) -> Option<usize> {
+    let mut qi = 0;
+    let mut last = start;
+
+    for (i, &c) in cand.iter().enumerate().skip(start) {
+        if qi < q.len() && c.to_ascii_lowercase() == q[qi].to_ascii_lowercase() {
+            last = i;
+            qi += 1;
+
+            if qi == q.len() {
+                return Some(last);
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn non_subsequence_does_not_match() {
+        assert_eq!(score("work/plan", "xyz"), None);
+    }
+
+    #[test]
+    fn empty_query_matches_everything() {
+        assert_eq!(score("anything", ""), Some(0));
+    }
+
+    #[test]
+    fn word_boundary_bonus_outranks_mid_word_match() {
+        // "wp" is a subsequence of both, but only in "work/plan" does each
+        // letter land on a word boundary (start of "work", start of
+        // "plan"); in "swap" both letters are mid-word.
+        let names = vec!["swap".to_string(), "work/plan".to_string()];
+        let ranked = rank(names.iter(), "wp");
+
+        assert_eq!(ranked, vec![&names[1], &names[0]]);
+    }
+}
@@ -0,0 +1,102 @@
+//! A rolling daily journal note, with size-based rotation modeled on
+//! Mercurial's `LogFile` utility: entries are appended until the file
+//! exceeds `max_size`, at which point it is rotated out to `.1`, `.1` to
+//! `.2`, and so on up to `max_files`, discarding the oldest.
+
+use crate::config::Config;
+use anyhow::Result;
+use chrono::Local;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const JOURNAL_NAME: &str = "journal";
+
+fn journal_path(cfg: &Config) -> PathBuf {
+    Path::new(JOURNAL_NAME).with_extension(&cfg.extension)
+}
+
+fn numbered(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{n}"));
+    PathBuf::from(name)
+}
+
+/// Remove any already-rotated `path.N` with `N > max_files`, left over from
+/// a previous run where `max_files` was larger than it is now.
+fn prune_stale(path: &Path, max_files: usize) -> Result<()> {
+    let dir = match path.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p,
+        _ => Path::new("."),
+    };
+    let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+        return Ok(());
+    };
+    let prefix = format!("{file_name}.");
+
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let Some(name) = entry.file_name().to_str().map(str::to_owned) else {
+            continue;
+        };
+        let Some(suffix) = name.strip_prefix(&prefix) else {
+            continue;
+        };
+        if let Ok(n) = suffix.parse::<usize>() {
+            if n > max_files {
+                fs::remove_file(entry.path())?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Shift `path` -> `path.1` -> `path.2` ... up to `max_files`, discarding
+/// whatever was already at the oldest slot.
+fn rotate(path: &Path, max_files: usize) -> Result<()> {
+    if max_files == 0 {
+        fs::remove_file(path)?;
+        return Ok(());
+    }
+
+    prune_stale(path, max_files)?;
+
+    let oldest = numbered(path, max_files);
+    if oldest.is_file() {
+        fs::remove_file(&oldest)?;
+    }
+
+    for n in (1..max_files).rev() {
+        let from = numbered(path, n);
+        if from.is_file() {
+            fs::rename(&from, numbered(path, n + 1))?;
+        }
+    }
+
+    fs::rename(path, numbered(path, 1))?;
+
+    Ok(())
+}
+
+/// Append `entry`, timestamped, to the rolling journal note, creating
+/// `notes_dir` if needed and rotating the journal first if it has grown
+/// past `cfg.max_size`. Returns the journal's path relative to `notes_dir`.
+pub fn append(entry: &str, cfg: &Config) -> Result<PathBuf> {
+    fs::create_dir_all(&cfg.notes_dir)?;
+
+    let rel = journal_path(cfg);
+    let path = cfg.notes_dir.join(&rel);
+
+    if let Ok(meta) = fs::metadata(&path) {
+        if meta.len() > cfg.max_size {
+            rotate(&path, cfg.max_files)?;
+        }
+    }
+
+    let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S");
+    let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+    writeln!(file, "[{timestamp}] {entry}")?;
+
+    Ok(rel)
+}
@@ -1,25 +1,23 @@
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use clap::{Args, Parser, Subcommand};
 use colored::Colorize;
-use directories::UserDirs;
 use glob::glob;
-use serde_derive::{Deserialize, Serialize};
-use std::collections::HashMap;
+use regex::{Regex, RegexBuilder};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::ffi::OsStr;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
-static PROGNAME: &'static str = env!("CARGO_PKG_NAME");
+mod config;
+mod fuzzy;
+mod journal;
+mod picker;
+mod sources;
 
-#[derive(Debug, Serialize, Deserialize)]
-struct Config {
-    conf: PathBuf,
-    editor: String,
-    notes_dir: PathBuf,
-    extension: String,
-    viewer: String,
-}
+use config::Config;
+
+pub(crate) static PROGNAME: &str = env!("CARGO_PKG_NAME");
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -33,6 +31,15 @@ struct Cli {
     #[arg(short, long)]
     conf: bool,
 
+    /// Only use the project-local .rnotes.toml (discovered by walking up
+    /// from the current directory), ignoring the global config
+    #[arg(long, conflicts_with = "global")]
+    local: bool,
+
+    /// Only use the global config, ignoring any project-local .rnotes.toml
+    #[arg(long)]
+    global: bool,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -52,32 +59,59 @@ enum Commands {
     Find(NoteArgs),
 
     /// Grep in notes content
-    Grep(NoteArgs),
+    Grep(GrepArgs),
 
     /// Open the note in the editor
     Open(NoteArgs),
+
+    /// Append a timestamped entry to the rolling daily journal note
+    Journal(JournalArgs),
 }
 
 #[derive(Args)]
 struct NoteArgs {
-    note_arg: String,
-}
-
-impl Default for Config {
-    fn default() -> Self {
-        Self {
-            editor: "vim".into(),
-            extension: "md".into(),
-            viewer: "cat".into(),
-            conf: confy::get_configuration_file_path(PROGNAME, PROGNAME)
-                .expect("Failed to get configuration file"),
-            notes_dir: UserDirs::new()
-                .expect("Failed to get user directory")
-                .home_dir()
-                .join(PROGNAME)
-                .to_path_buf(),
-        }
+    /// Note name; for cat/open/find, omit (or pass --pick) to choose from a
+    /// fuzzy picker. Required for new
+    note_arg: Option<String>,
+
+    /// Use the interactive fuzzy picker even if a note name is given
+    /// (ignored by new)
+    #[arg(long)]
+    pick: bool,
+}
+
+#[derive(Args)]
+struct GrepArgs {
+    /// Pattern to search for (regex)
+    pattern: String,
+
+    /// Case-insensitive match
+    #[arg(short = 'i', long)]
+    ignore_case: bool,
+
+    /// Match whole words only
+    #[arg(short = 'w', long)]
+    word: bool,
+
+    /// Show N lines of context before and after each match
+    #[arg(short = 'C', long = "context", value_name = "N", default_value_t = 0)]
+    context: usize,
+}
+
+#[derive(Args)]
+struct JournalArgs {
+    /// Text of the journal entry
+    entry: Vec<String>,
+}
+
+/// Resolve a note name from `arg`, falling back to the interactive fuzzy
+/// picker when no name was given or `--pick` was passed.
+fn resolve_note_arg(arg: &NoteArgs, notes: &HashMap<String, PathBuf>) -> Result<Option<String>> {
+    if arg.pick || arg.note_arg.is_none() {
+        return Ok(picker::pick(notes)?.map(|(name, _)| name));
     }
+
+    Ok(arg.note_arg.clone())
 }
 
 fn do_ls(notes: &HashMap<String, PathBuf>) -> ! {
@@ -110,10 +144,34 @@ where
     Ok(())
 }
 
+/// Build a note's relative path from a user-supplied name, rejecting `..`
+/// and absolute components so a nested note name can't escape `notes_dir`.
+fn safe_note_path(note_name: &str, extension: &str) -> Result<PathBuf> {
+    let note_file = Path::new(note_name).with_extension(extension);
+
+    if note_file.components().any(|c| {
+        matches!(
+            c,
+            std::path::Component::ParentDir
+                | std::path::Component::RootDir
+                | std::path::Component::Prefix(_)
+        )
+    }) {
+        bail!("note name must be a relative path without `..`: {note_name}");
+    }
+
+    Ok(note_file)
+}
+
 fn do_new(note_name: &str, cfg: &Config) -> Result<()> {
-    fs::create_dir_all(&cfg.notes_dir)?;
+    let note_file = safe_note_path(note_name, &cfg.extension)?;
 
-    let note_file = Path::new(&note_name).with_extension(&cfg.extension);
+    match note_file.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => {
+            fs::create_dir_all(cfg.notes_dir.join(parent))?;
+        }
+        _ => fs::create_dir_all(&cfg.notes_dir)?,
+    }
 
     _cmd(&cfg.editor, &cfg.notes_dir, &note_file)?;
 
@@ -143,49 +201,177 @@ fn do_cmd(
     Ok(())
 }
 
-fn do_find(note_arg: &str, notes: &HashMap<String, PathBuf>) {
+fn do_cat(note_arg: &str, notes: &HashMap<String, PathBuf>, cfg: &Config) -> Result<()> {
+    if notes.contains_key(note_arg) {
+        return do_cmd(note_arg, notes, cfg, &cfg.viewer);
+    }
+
+    let enabled = sources::enabled(&cfg.remote_sources);
+    let Some(body) = sources::fetch_first(&enabled, note_arg) else {
+        eprintln!("{note_arg} not found locally or in any configured source");
+        std::process::exit(exitcode::UNAVAILABLE);
+    };
+
+    let note_file = cache_remote_hit(note_arg, &body, cfg)?;
+    _cmd(&cfg.viewer, &cfg.notes_dir, &note_file)?;
+
+    Ok(())
+}
+
+fn cache_remote_hit(query: &str, body: &str, cfg: &Config) -> Result<PathBuf> {
+    let note_file = safe_note_path(query, &cfg.extension)?;
+
+    if cfg.cache_remote {
+        match note_file.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => {
+                fs::create_dir_all(cfg.notes_dir.join(parent))?;
+            }
+            _ => fs::create_dir_all(&cfg.notes_dir)?,
+        }
+
+        fs::write(cfg.notes_dir.join(&note_file), body)?;
+
+        Ok(note_file)
+    } else {
+        let tmp_file = std::env::temp_dir().join(&note_file);
+
+        if let Some(parent) = tmp_file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&tmp_file, body)?;
+
+        Ok(tmp_file)
+    }
+}
+
+fn do_find(note_arg: &str, notes: &HashMap<String, PathBuf>) -> bool {
     println!("");
-    notes
-        .iter()
-        .filter(|x| x.0.contains(&note_arg))
-        .for_each(|x| println!("{}", x.0.bold()));
+
+    let mut found = false;
+    for (name, _) in notes.iter().filter(|x| x.0.contains(&note_arg)) {
+        println!("{}", name.bold());
+        found = true;
+    }
+
+    found
 }
 
-fn do_grep(note_arg: &str, notes: &HashMap<String, PathBuf>) -> Result<()> {
-    for (note, path) in notes {
-        let mut nl: bool = true;
+fn do_find_arg(arg: &NoteArgs, notes: &HashMap<String, PathBuf>, cfg: &Config) -> Result<()> {
+    if arg.pick || arg.note_arg.is_none() {
+        if let Some((name, _)) = picker::pick(notes)? {
+            println!("");
+            println!("{}", name.bold());
+        }
 
-        fs::read_to_string(path)?
-            .lines()
-            .filter(|l| l.contains(&note_arg))
-            .for_each(move |l| {
-                if nl == true {
-                    println!("");
-                    nl = false;
-                }
-                println!("{}: {}", note.bold(), l);
-            });
+        return Ok(());
+    }
+
+    let note_arg = arg.note_arg.as_ref().expect("checked above");
+
+    if do_find(note_arg, notes) {
+        return Ok(());
+    }
+
+    let enabled = sources::enabled(&cfg.remote_sources);
+    if let Some(body) = sources::fetch_first(&enabled, note_arg) {
+        let note_file = cache_remote_hit(note_arg, &body, cfg)?;
+        println!("{} {}", "(remote)".dimmed(), note_file.display());
+    }
+
+    Ok(())
+}
+
+fn compile_grep_pattern(args: &GrepArgs) -> Result<Regex> {
+    let pattern = if args.word {
+        format!(r"\b(?:{})\b", args.pattern)
+    } else {
+        args.pattern.clone()
+    };
+
+    RegexBuilder::new(&pattern)
+        .case_insensitive(args.ignore_case)
+        .build()
+        .context("Invalid grep pattern")
+}
+
+fn highlight_matches(re: &Regex, line: &str) -> String {
+    let mut out = String::new();
+    let mut last = 0;
+
+    for m in re.find_iter(line) {
+        out.push_str(&line[last..m.start()]);
+        out.push_str(&line[m.start()..m.end()].red().bold().to_string());
+        last = m.end();
+    }
+    out.push_str(&line[last..]);
+
+    out
+}
+
+fn do_grep(args: &GrepArgs, notes: &HashMap<String, PathBuf>) -> Result<()> {
+    let re = compile_grep_pattern(args)?;
+
+    let mut names: Vec<&String> = notes.keys().collect();
+    names.sort();
+
+    for note in names {
+        let contents = fs::read_to_string(&notes[note])?;
+        let lines: Vec<&str> = contents.lines().collect();
+
+        let hits: HashSet<usize> = lines
+            .iter()
+            .enumerate()
+            .filter(|(_, l)| re.is_match(l))
+            .map(|(i, _)| i)
+            .collect();
+
+        if hits.is_empty() {
+            continue;
+        }
+
+        println!("");
+        println!("{}", note.bold());
+
+        let mut shown: BTreeSet<usize> = BTreeSet::new();
+        for &hit in &hits {
+            let start = hit.saturating_sub(args.context);
+            let end = (hit + args.context).min(lines.len().saturating_sub(1));
+            shown.extend(start..=end);
+        }
+
+        for i in shown {
+            let rendered = if hits.contains(&i) {
+                highlight_matches(&re, lines[i])
+            } else {
+                lines[i].to_string()
+            };
+
+            println!("{}: {}", (i + 1).to_string().dimmed(), rendered);
+        }
     }
 
     Ok(())
 }
 
 fn build_notes(cfg: &Config) -> Result<HashMap<String, PathBuf>> {
-    let notes = cfg
+    let pattern = cfg
         .notes_dir
+        .join("**")
         .join("*")
         .with_extension(&cfg.extension)
         .into_os_string();
 
-    let c = glob(&notes.to_string_lossy())?
+    let c = glob(&pattern.to_string_lossy())?
         .map(|p| {
             let note_path = p?;
             let note_name = note_path
-                .file_stem()
-                .context("Failed to get file stem")?
-                .to_str()
-                .context("Failed to get file name")?
-                .to_owned();
+                .strip_prefix(&cfg.notes_dir)
+                .context("Failed to get relative note path")?
+                .with_extension("")
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .collect::<Vec<String>>()
+                .join("/");
             Ok((note_name, note_path))
         })
         .collect::<Result<Vec<(String, PathBuf)>>>();
@@ -195,44 +381,93 @@ fn build_notes(cfg: &Config) -> Result<HashMap<String, PathBuf>> {
     Ok(m)
 }
 
-fn do_print_config(cfg: &Config) -> ! {
+fn do_print_config(cfg: &Config, sources: &config::Sources) -> ! {
     println!("");
+    match &sources.conf {
+        config::Source::Default => println!(
+            "Configuration file: \t{} \t[not loaded, --local in effect]",
+            format!("{}", cfg.conf.display()).dimmed()
+        ),
+        config::Source::Global(_) | config::Source::Local(_) => println!(
+            "Configuration file: \t{} \t[{}]",
+            format!("{}", cfg.conf.display()).bold(),
+            sources.conf
+        ),
+    }
     println!(
-        "Configuration file: \t{}",
-        format!("{}", cfg.conf.display()).bold()
+        "Notes directory: \t{} \t[{}]",
+        format!("{}", cfg.notes_dir.display()).bold(),
+        sources.notes_dir
     );
     println!(
-        "Notes directory: \t{}",
-        format!("{}", cfg.notes_dir.display()).bold()
+        "Editor: \t\t{} \t[{}]",
+        cfg.editor.bold(),
+        sources.editor
+    );
+    println!(
+        "Viewer: \t\t{} \t[{}]",
+        cfg.viewer.bold(),
+        sources.viewer
+    );
+    println!(
+        "Notes extension: \t{} \t[{}]",
+        cfg.extension.bold(),
+        sources.extension
+    );
+    println!(
+        "Remote sources: \t{} \t[{}]",
+        cfg.remote_sources.join(", ").bold(),
+        sources.remote_sources
+    );
+    println!(
+        "Cache remote hits: \t{} \t[{}]",
+        cfg.cache_remote.to_string().bold(),
+        sources.cache_remote
+    );
+    println!(
+        "Journal max size: \t{} \t[{}]",
+        cfg.max_size.to_string().bold(),
+        sources.max_size
+    );
+    println!(
+        "Journal max files: \t{} \t[{}]",
+        cfg.max_files.to_string().bold(),
+        sources.max_files
     );
-    println!("Editor: \t\t{}", cfg.editor.bold());
-    println!("Viewer: \t\t{}", cfg.viewer.bold());
-    println!("Notes extension: \t{}", cfg.extension.bold());
 
     std::process::exit(exitcode::OK);
 }
 
 fn main() -> Result<()> {
-    let cfg = confy::load(PROGNAME, PROGNAME)?;
     let cli = Cli::parse();
+    let loaded = config::load(cli.local, cli.global)?;
+    let cfg = loaded.config;
 
     if cli.conf {
-        do_print_config(&cfg);
+        do_print_config(&cfg, &loaded.sources);
     }
 
     let notes = build_notes(&cfg)?;
 
     match cli.command {
         Some(Commands::New(arg)) => {
-            do_new(&arg.note_arg, &cfg)?;
+            let note_arg = arg
+                .note_arg
+                .as_deref()
+                .context("note name is required for new")?;
+            do_new(note_arg, &cfg)?;
         }
 
         Some(Commands::Cat(arg)) => {
-            do_cmd(&arg.note_arg, &notes, &cfg, &cfg.viewer)?;
+            if let Some(note_arg) = resolve_note_arg(&arg, &notes)? {
+                do_cat(&note_arg, &notes, &cfg)?;
+            }
         }
 
         Some(Commands::Open(arg)) => {
-            do_cmd(&arg.note_arg, &notes, &cfg, &cfg.editor)?;
+            if let Some(note_arg) = resolve_note_arg(&arg, &notes)? {
+                do_cmd(&note_arg, &notes, &cfg, &cfg.editor)?;
+            }
         }
 
         Some(Commands::Ls) => {
@@ -240,11 +475,22 @@ fn main() -> Result<()> {
         }
 
         Some(Commands::Find(arg)) => {
-            do_find(&arg.note_arg, &notes);
+            do_find_arg(&arg, &notes, &cfg)?;
         }
 
         Some(Commands::Grep(arg)) => {
-            do_grep(&arg.note_arg, &notes)?;
+            do_grep(&arg, &notes)?;
+        }
+
+        Some(Commands::Journal(arg)) => {
+            let entry = arg.entry.join(" ");
+            let note_file = journal::append(&entry, &cfg)?;
+
+            println!("");
+            println!(
+                "Appended to {}",
+                format!("{}", note_file.display()).bold()
+            );
         }
 
         _ => {}
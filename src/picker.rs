@@ -0,0 +1,156 @@
+//! Interactive fuzzy picker used by `Find`/`Open`/`Cat` when invoked
+//! without an exact note name.
+
+use crate::fuzzy;
+use anyhow::Result;
+use colored::Colorize;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::terminal;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+const MAX_VISIBLE: usize = 10;
+
+/// Present `notes` as a fuzzy-filterable list, re-ranking on every
+/// keystroke, and return the `(name, path)` of the selected note. Returns
+/// `None` if the user cancelled with Esc or Ctrl-C.
+pub fn pick(notes: &HashMap<String, PathBuf>) -> Result<Option<(String, PathBuf)>> {
+    let names: Vec<&String> = notes.keys().collect();
+
+    let mut query = String::new();
+    let mut selected: usize = 0;
+
+    terminal::enable_raw_mode()?;
+    let picked = run(&names, &mut query, &mut selected);
+    terminal::disable_raw_mode()?;
+
+    let picked = picked?;
+
+    Ok(picked.and_then(|name| notes.get_key_value(&name).map(|(n, p)| (n.clone(), p.clone()))))
+}
+
+fn run(names: &[&String], query: &mut String, selected: &mut usize) -> Result<Option<String>> {
+    let mut offset: usize = 0;
+
+    let result = loop {
+        let matches = fuzzy::rank(names.iter().copied(), query);
+        *selected = (*selected).min(matches.len().saturating_sub(1));
+        offset = scroll_offset(offset, *selected, matches.len());
+
+        render(query, &matches, *selected, offset)?;
+
+        match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
+                KeyCode::Esc => break None,
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    break None;
+                }
+                KeyCode::Enter => break matches.get(*selected).map(|n| (*n).clone()),
+                KeyCode::Up => *selected = selected.saturating_sub(1),
+                KeyCode::Down => {
+                    *selected = (*selected + 1).min(matches.len().saturating_sub(1));
+                }
+                KeyCode::Backspace => {
+                    query.pop();
+                    *selected = 0;
+                    offset = 0;
+                }
+                KeyCode::Char(c) => {
+                    query.push(c);
+                    *selected = 0;
+                    offset = 0;
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    };
+
+    clear()?;
+
+    Ok(result)
+}
+
+/// Slide `offset` so `selected` stays within the `MAX_VISIBLE`-row window,
+/// clamped so the window never runs past the end of `len` items.
+fn scroll_offset(offset: usize, selected: usize, len: usize) -> usize {
+    let mut offset = if selected < offset {
+        selected
+    } else if selected >= offset + MAX_VISIBLE {
+        selected + 1 - MAX_VISIBLE
+    } else {
+        offset
+    };
+
+    let max_offset = len.saturating_sub(MAX_VISIBLE);
+    if offset > max_offset {
+        offset = max_offset;
+    }
+
+    offset
+}
+
+/// Render one frame, showing the `MAX_VISIBLE`-row window starting at
+/// `offset`. Always redraws exactly `MAX_VISIBLE` result lines (blanking
+/// out any that have no match this frame) so a shrinking result set can't
+/// leave stale entries from a previous, longer frame on screen.
+fn render(query: &str, matches: &[&String], selected: usize, offset: usize) -> Result<()> {
+    let mut out = io::stdout();
+
+    print!("\r\x1b[2K> {query}\r\n");
+    for row in 0..MAX_VISIBLE {
+        let idx = offset + row;
+        print!("\x1b[2K");
+        match matches.get(idx) {
+            Some(name) if idx == selected => println!("\r{}", name.black().on_white()),
+            Some(name) => println!("\r{name}"),
+            None => println!("\r"),
+        }
+    }
+    print!("\x1b[{}A", MAX_VISIBLE + 1);
+    out.flush()?;
+
+    Ok(())
+}
+
+/// Blank out the rendered block and park the cursor back at its top-left
+/// corner, so whatever the caller prints next doesn't get interleaved with
+/// leftover picker rows.
+fn clear() -> Result<()> {
+    let mut out = io::stdout();
+
+    for _ in 0..=MAX_VISIBLE {
+        print!("\r\x1b[2K\n");
+    }
+    print!("\x1b[{}A\r", MAX_VISIBLE + 1);
+    out.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offset_follows_selection_past_the_visible_window() {
+        // 20 candidates, window of MAX_VISIBLE (10): selecting row 15 must
+        // slide the window so it's visible, not just clamp `selected`.
+        let offset = scroll_offset(0, 15, 20);
+        assert!(offset <= 15 && 15 < offset + MAX_VISIBLE);
+
+        // Scrolling back up to row 2 must slide the window up so that row 2
+        // is its first visible row.
+        let offset = scroll_offset(offset, 2, 20);
+        assert_eq!(offset, 2);
+    }
+
+    #[test]
+    fn offset_never_runs_past_the_end_of_the_list() {
+        // Selecting the very last of 12 items shouldn't push the window
+        // past the list's end.
+        let offset = scroll_offset(0, 11, 12);
+        assert_eq!(offset, 2);
+    }
+}
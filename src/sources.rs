@@ -0,0 +1,83 @@
+//! Read-only remote cheatsheet sources, used as a fallback by `Cat` when a
+//! note isn't found locally. Modeled on navi's pluggable `clients`
+//! (cheatsh, tldr).
+
+use anyhow::{bail, Result};
+
+/// A remote knowledge base that can answer a single query with a page of
+/// text.
+pub trait Source {
+    /// Name used in config (`remote_sources = ["cheatsh", "tldr"]`).
+    fn name(&self) -> &'static str;
+
+    /// Fetch a page of content for `query`, erroring if there is none.
+    fn fetch(&self, query: &str) -> Result<String>;
+}
+
+pub struct CheatSh;
+
+impl Source for CheatSh {
+    fn name(&self) -> &'static str {
+        "cheatsh"
+    }
+
+    fn fetch(&self, query: &str) -> Result<String> {
+        // `?T` strips the ANSI escapes cheat.sh adds for terminal clients,
+        // since the result is handed off to `viewer` rather than printed
+        // directly.
+        let url = format!("https://cheat.sh/{query}?T");
+        let body = ureq::get(&url).call()?.into_string()?;
+
+        if body.trim().is_empty() || body.contains("Unknown topic.") {
+            bail!("cheat.sh has no page for `{query}`");
+        }
+
+        Ok(body)
+    }
+}
+
+/// Platform directories to try, in order, since most pages live under a
+/// platform rather than `common`.
+const TLDR_PLATFORMS: &[&str] = &["common", "linux", "osx", "windows", "android", "sunos"];
+
+pub struct Tldr;
+
+impl Source for Tldr {
+    fn name(&self) -> &'static str {
+        "tldr"
+    }
+
+    fn fetch(&self, query: &str) -> Result<String> {
+        for platform in TLDR_PLATFORMS {
+            let url = format!(
+                "https://raw.githubusercontent.com/tldr-pages/tldr/main/pages/{platform}/{query}.md"
+            );
+
+            if let Ok(resp) = ureq::get(&url).call() {
+                let body = resp.into_string()?;
+                if !body.trim().is_empty() {
+                    return Ok(body);
+                }
+            }
+        }
+
+        bail!("tldr has no page for `{query}` in any platform directory ({TLDR_PLATFORMS:?})");
+    }
+}
+
+/// Build the sources enabled by `names`, in order, ignoring unknown names.
+pub fn enabled(names: &[String]) -> Vec<Box<dyn Source>> {
+    names
+        .iter()
+        .filter_map(|name| match name.as_str() {
+            "cheatsh" => Some(Box::new(CheatSh) as Box<dyn Source>),
+            "tldr" => Some(Box::new(Tldr) as Box<dyn Source>),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Query `sources` in order and return the first hit.
+pub fn fetch_first(sources: &[Box<dyn Source>], query: &str) -> Option<String> {
+    sources.iter().find_map(|s| s.fetch(query).ok())
+}